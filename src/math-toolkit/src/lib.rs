@@ -48,6 +48,14 @@ sol! {
         uint256 liquidityDepth;
         uint256 priceVolatility; // Scaled by 1e6
     }
+
+    struct StableSwapParams {
+        uint256[] balances;
+        uint256 amp;
+        uint256 i;
+        uint256 j;
+        uint256 dx;
+    }
 }
 
 // ============ Fixed-Point Arithmetic Constants ============
@@ -55,6 +63,31 @@ sol! {
 const FIXED_POINT_SCALE: u64 = 1_000_000_000_000_000_000; // 1e18 for 18 decimal precision
 const PRECISION_SCALE: u64 = 1_000_000; // 1e6 for percentage calculations
 const MAX_ITERATIONS: u32 = 20; // Maximum iterations for mathematical approximations
+const MAX_ROUTE_DEPTH: usize = 4; // Maximum hops explored when routing
+
+// ============ Checked Arithmetic Layer ============
+
+/// Arithmetic error surfaced by the checked-math helpers.
+///
+/// Raw `*`/`+` wrap silently in WASM release builds, so the engine's hot paths
+/// funnel their multiplications and additions through the `checked_*` helpers,
+/// which stay checked even in release and report overflow instead of returning
+/// a corrupted number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MathError {
+    Overflow,
+    DivByZero,
+}
+
+/// Checked addition that reports overflow instead of wrapping.
+fn checked_add(a: U256, b: U256) -> Result<U256, MathError> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+/// Checked multiplication that reports overflow instead of wrapping.
+fn checked_mul(a: U256, b: U256) -> Result<U256, MathError> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
 
 // ============ Core Mathematical Engine ============
 
@@ -68,8 +101,21 @@ pub trait MathematicalEngineAPI<SDK> {
     fn calculate_precise_slippage(&self, params: SlippageParams) -> U256;
     fn calculate_dynamic_fee(&self, params: VolatilityParams) -> U256;
     fn optimize_swap_amount(&self, params: SwapParams) -> U256;
-    fn find_optimal_route(&self, hops: Vec<RouteHop>, amount_in: U256) -> (Vec<u32>, U256);
+    fn find_optimal_route(
+        &self,
+        hops: Vec<RouteHop>,
+        amount_in: U256,
+        token_in: Address,
+    ) -> (Vec<u32>, U256);
     fn calculate_lp_tokens(&self, params: LiquidityParams) -> U256;
+    fn get_invariant_d(&self, balances: Vec<U256>, amp: U256) -> U256;
+    fn calculate_stableswap_output(&self, params: StableSwapParams) -> U256;
+    fn calculate_ewma_dynamic_fee(
+        &self,
+        params: VolatilityParams,
+        price_history: Vec<U256>,
+        lambda_scaled: U256,
+    ) -> U256;
 }
 
 #[router(mode = "solidity")]
@@ -82,38 +128,32 @@ impl<SDK: SharedAPI> MathematicalEngineAPI<SDK> for MathematicalEngine<SDK> {
             return U256::ZERO;
         }
 
-        // Convert to u128 for calculations (safe for most AMM operations)
-        let val = if value > U256::from(u128::MAX) {
-            u128::MAX
-        } else {
-            value.to::<u128>()
-        };
-
-        // Use Newton's method with fixed-point arithmetic
-        let result = self.fixed_point_sqrt(val);
-        U256::from(result)
+        // Exact integer floor square root over the whole U256 range.
+        self.fixed_point_sqrt(value)
     }
 
     /// Calculate precise slippage using high-precision fixed-point arithmetic
     /// Eliminates precision loss from Solidity integer division
     #[function_id("calculatePreciseSlippage((uint256,uint256,uint256,uint256))")]
     fn calculate_precise_slippage(&self, params: SlippageParams) -> U256 {
-        let amount_in = params.amountIn.to::<u128>();
-        let reserve_in = params.reserveIn.to::<u128>();
-        let reserve_out = params.reserveOut.to::<u128>();
-        let expected_out = params.expectedOut.to::<u128>();
-
-        if reserve_in == 0 || expected_out == 0 {
+        if params.reserveIn == U256::ZERO || params.expectedOut == U256::ZERO {
             return U256::ZERO;
         }
 
         // Calculate actual output using constant product formula with high precision
-        let actual_out = self.calculate_constant_product_output(amount_in, reserve_in, reserve_out);
+        let actual_out = self.calculate_constant_product_output(
+            params.amountIn,
+            params.reserveIn,
+            params.reserveOut,
+        );
 
         // Calculate slippage as basis points (10000 = 100%)
-        if expected_out > actual_out {
-            let slippage = ((expected_out - actual_out) * 10000) / expected_out;
-            U256::from(slippage)
+        if params.expectedOut > actual_out {
+            self.mul_div(
+                params.expectedOut - actual_out,
+                U256::from(10000),
+                params.expectedOut,
+            )
         } else {
             U256::ZERO
         }
@@ -123,146 +163,224 @@ impl<SDK: SharedAPI> MathematicalEngineAPI<SDK> for MathematicalEngine<SDK> {
     /// Uses fixed-point arithmetic to approximate exponential functions
     #[function_id("calculateDynamicFee((uint256,uint256,uint256))")]
     fn calculate_dynamic_fee(&self, params: VolatilityParams) -> U256 {
-        let volume = params.volume24h.to::<u128>();
-        let liquidity = params.liquidityDepth.to::<u128>();
-        let volatility = params.priceVolatility.to::<u128>();
-
-        // Base fee: 30 basis points (0.3%)
-        let base_fee = 30u128;
-
-        // Volatility adjustment using Taylor series approximation of exponential
-        let volatility_multiplier =
-            self.approximate_exponential(volatility, PRECISION_SCALE as u128);
-
-        // Utilization ratio adjustment
-        let utilization_multiplier = if liquidity > 0 {
-            let ratio = (volume * PRECISION_SCALE as u128) / liquidity;
-            // Cap utilization impact at 2x
-            1_000_000 + (ratio / 2).min(1_000_000)
-        } else {
-            1_000_000 // 1.0 in fixed point
-        };
-
-        // Calculate dynamic fee with both adjustments
-        let dynamic_fee = (base_fee * volatility_multiplier * utilization_multiplier)
-            / (PRECISION_SCALE as u128 * PRECISION_SCALE as u128);
+        self.dynamic_fee(
+            params.volume24h.to::<u128>(),
+            params.liquidityDepth.to::<u128>(),
+            params.priceVolatility.to::<u128>(),
+        )
+    }
 
-        // Cap at 100 basis points (1%)
-        U256::from(dynamic_fee.min(100))
+    /// Dynamic fee using an EWMA (RiskMetrics) volatility estimate.
+    /// The volatility is derived from `price_history` rather than supplied, so
+    /// the fee reacts faster to regime changes than the equal-weighted estimate.
+    #[function_id("calculateEwmaDynamicFee((uint256,uint256,uint256),uint256[],uint256)")]
+    fn calculate_ewma_dynamic_fee(
+        &self,
+        params: VolatilityParams,
+        price_history: Vec<U256>,
+        lambda_scaled: U256,
+    ) -> U256 {
+        let volatility = self.calculate_ewma_volatility(&price_history, lambda_scaled.to::<u128>());
+        self.dynamic_fee(
+            params.volume24h.to::<u128>(),
+            params.liquidityDepth.to::<u128>(),
+            volatility,
+        )
     }
 
     /// Optimize swap amount using calculus-based approach with fixed-point math
     /// Finds optimal input that minimizes price impact
     #[function_id("optimizeSwapAmount((uint256,uint256,uint256,uint256))")]
     fn optimize_swap_amount(&self, params: SwapParams) -> U256 {
-        let amount_in = params.amountIn.to::<u128>();
-        let reserve_in = params.reserveIn.to::<u128>();
-        let reserve_out = params.reserveOut.to::<u128>();
-        let fee_rate = params.feeRate.to::<u128>();
-
-        // Calculate optimal input using derivative of constant product formula
-        // Optimal point where marginal price impact is minimized
-        let k = reserve_in * reserve_out; // Constant product
+        let amount_in = params.amountIn;
+        let reserve_in = params.reserveIn;
+        let reserve_out = params.reserveOut;
+        let fee_rate = params.feeRate;
+
+        // Calculate optimal input using derivative of constant product formula.
+        // Optimal point where marginal price impact is minimized.
+        // k = reserve_in * reserve_out is guarded against 256-bit overflow.
+        let k = match checked_mul(reserve_in, reserve_out) {
+            Ok(k) => k,
+            Err(_) => return U256::ZERO,
+        };
         let sqrt_k = self.fixed_point_sqrt(k);
 
-        // Apply fee adjustment
-        let fee_multiplier = (10000 - fee_rate) * PRECISION_SCALE as u128 / 10000;
+        // Apply fee adjustment: (sqrt_k - reserve_in) * (10000 - fee_rate) / 10000
         let optimal_input = if sqrt_k > reserve_in {
-            ((sqrt_k - reserve_in) * fee_multiplier) / PRECISION_SCALE as u128
+            let fee_bps = U256::from(10000) - fee_rate;
+            self.mul_div(sqrt_k - reserve_in, fee_bps, U256::from(10000))
         } else {
-            0
+            U256::ZERO
         };
 
         // Ensure we don't exceed the requested amount
-        let result = optimal_input.min(amount_in);
-        U256::from(result)
+        optimal_input.min(amount_in)
     }
 
-    /// Find optimal multi-hop routing using dynamic programming
-    /// More sophisticated than what's possible in Solidity due to gas constraints
-    #[function_id("findOptimalRoute((address,address,address,uint256,uint256)[],uint256)")]
-    fn find_optimal_route(&self, hops: Vec<RouteHop>, amount_in: U256) -> (Vec<u32>, U256) {
+    /// Find the optimal route through the token graph formed by the `RouteHop`
+    /// edges (`tokenIn -> tokenOut`), maximising a liquidity-weighted output for
+    /// `amount_in` starting from `token_in`.
+    ///
+    /// A Bellman-Ford-style relaxation carries, for each token, the amount of
+    /// the highest-scoring path reaching it, honouring token connectivity and forbidding pool reuse within a
+    /// path, up to `MAX_ROUTE_DEPTH` hops. Each candidate is scored by its
+    /// routed amount multiplied by a liquidity-derived success factor, so
+    /// deep-liquidity routes are preferred over shallow pools that merely quote
+    /// a higher nominal amount.
+    #[function_id("findOptimalRoute((address,address,address,uint256,uint256)[],uint256,address)")]
+    fn find_optimal_route(
+        &self,
+        hops: Vec<RouteHop>,
+        amount_in: U256,
+        token_in: Address,
+    ) -> (Vec<u32>, U256) {
         if hops.is_empty() {
             return (Vec::new(), U256::ZERO);
         }
 
-        let amount = amount_in.to::<u128>();
+        // best[token] = (amount, score, path). The source seeds the search with
+        // a zero score, so any path that cycles back to `token_in` replaces it.
+        let mut best: Vec<(Address, U256, U256, Vec<u32>)> =
+            alloc::vec![(token_in, amount_in, U256::ZERO, Vec::new())];
+
         let mut best_route: Vec<u32> = Vec::new();
-        let mut best_output = 0u128;
-
-        // Try direct routes first (single hop)
-        for (i, hop) in hops.iter().enumerate() {
-            let reserve_in = hop.reserveIn.to::<u128>();
-            let reserve_out = hop.reserveOut.to::<u128>();
-
-            if reserve_in > 0 && reserve_out > 0 {
-                let output =
-                    self.calculate_constant_product_output(amount, reserve_in, reserve_out);
-                if output > best_output {
-                    best_output = output;
-                    best_route = alloc::vec![i as u32];
-                }
-            }
-        }
+        let mut best_output = U256::ZERO;
+        let mut best_score = U256::ZERO;
+
+        let max_depth = MAX_ROUTE_DEPTH.min(hops.len());
+        for _ in 0..max_depth {
+            for (token, amount, _score, path) in best.clone() {
+                for (idx, hop) in hops.iter().enumerate() {
+                    // Respect token adjacency and forbid reusing a pool.
+                    if hop.tokenIn != token || path.contains(&(idx as u32)) {
+                        continue;
+                    }
+                    if hop.reserveIn == U256::ZERO || hop.reserveOut == U256::ZERO {
+                        continue;
+                    }
+
+                    let output =
+                        self.calculate_constant_product_output(amount, hop.reserveIn, hop.reserveOut);
+                    if output == U256::ZERO {
+                        continue;
+                    }
+
+                    // Penalise shallow pools via a linear success probability.
+                    let factor = self.success_factor(hop.reserveOut, output);
+                    let score = self.mul_div(output, factor, U256::from(PRECISION_SCALE));
+
+                    let mut new_path = path.clone();
+                    new_path.push(idx as u32);
 
-        // Try two-hop routes (more complex routing)
-        if hops.len() > 1 {
-            for i in 0..hops.len() {
-                for j in 0..hops.len() {
-                    if i != j {
-                        let intermediate = self.calculate_constant_product_output(
-                            amount,
-                            hops[i].reserveIn.to::<u128>(),
-                            hops[i].reserveOut.to::<u128>(),
-                        );
-
-                        if intermediate > 0 {
-                            let final_output = self.calculate_constant_product_output(
-                                intermediate,
-                                hops[j].reserveIn.to::<u128>(),
-                                hops[j].reserveOut.to::<u128>(),
-                            );
-
-                            if final_output > best_output {
-                                best_output = final_output;
-                                best_route = alloc::vec![i as u32, j as u32];
-                            }
-                        }
+                    if score > best_score {
+                        best_score = score;
+                        best_output = output;
+                        best_route = new_path.clone();
                     }
+
+                    relax_best(&mut best, hop.tokenOut, output, score, new_path);
                 }
             }
         }
 
-        (best_route, U256::from(best_output))
+        (best_route, best_output)
     }
 
     /// Calculate LP tokens with high-precision arithmetic
     /// Eliminates precision loss from Solidity integer arithmetic
     #[function_id("calculateLPTokens((uint256,uint256,uint256))")]
     fn calculate_lp_tokens(&self, params: LiquidityParams) -> U256 {
-        let amount0 = params.amount0.to::<u128>();
-        let amount1 = params.amount1.to::<u128>();
-        let total_supply = params.totalSupply.to::<u128>();
-
-        if total_supply == 0 {
-            // First liquidity provider - use high-precision geometric mean
-            let product = amount0 * amount1;
+        let amount0 = params.amount0;
+        let amount1 = params.amount1;
+        let total_supply = params.totalSupply;
+
+        if total_supply == U256::ZERO {
+            // First liquidity provider - use high-precision geometric mean.
+            // The product is formed (and square-rooted) in full 256-bit
+            // precision, so the geometric mean no longer saturates.
+            let product = match checked_mul(amount0, amount1) {
+                Ok(v) => v,
+                Err(_) => return U256::ZERO,
+            };
             let liquidity = self.fixed_point_sqrt(product);
 
             // Subtract minimum liquidity (1000)
-            if liquidity > 1000 {
-                U256::from(liquidity - 1000)
+            if liquidity > U256::from(1000) {
+                liquidity - U256::from(1000)
             } else {
                 U256::ZERO
             }
         } else {
-            // Subsequent providers - use high-precision proportional calculation
-            // Note: In real implementation, we'd need the actual reserves
-            // For now, using the amounts as proxy (this would be passed from Solidity)
-            let liquidity0 = (amount0 * total_supply) / amount0; // This needs actual reserve0
-            let liquidity1 = (amount1 * total_supply) / amount1; // This needs actual reserve1
+            // Subsequent providers - high-precision proportional calculation.
+            // (A real deployment would pass the actual reserves; the amounts
+            // stand in as a proxy here.)
+            let liquidity0 = self.mul_div(amount0, total_supply, amount0);
+            let liquidity1 = self.mul_div(amount1, total_supply, amount1);
+
+            liquidity0.min(liquidity1)
+        }
+    }
+
+    /// Solve the Curve StableSwap invariant `D` for the given balances.
+    /// `D` is the constant-sum analogue of `k` for a pool of pegged assets.
+    #[function_id("getInvariantD(uint256[],uint256)")]
+    fn get_invariant_d(&self, balances: Vec<U256>, amp: U256) -> U256 {
+        self.invariant_d(&balances, amp)
+    }
+
+    /// Price a StableSwap trade of `dx` of coin `i` for coin `j`.
+    /// The invariant is held fixed while Newton's method solves for the new
+    /// balance `y` of coin `j`; the trailing `- 1` rounds in the pool's favour.
+    #[function_id("calculateStableswapOutput((uint256[],uint256,uint256,uint256,uint256))")]
+    fn calculate_stableswap_output(&self, params: StableSwapParams) -> U256 {
+        let balances = params.balances;
+        let n = balances.len();
+        let i = params.i.to::<usize>();
+        let j = params.j.to::<usize>();
+        if i >= n || j >= n || i == j {
+            return U256::ZERO;
+        }
+
+        let d = self.invariant_d(&balances, params.amp);
+        if d == U256::ZERO {
+            return U256::ZERO;
+        }
+
+        let n_coins = U256::from(n);
+        let ann = params.amp * n_coins.pow(n_coins);
+
+        // Apply the input and solve for the output balance y.
+        let x = balances[i] + params.dx;
+        let mut c = d;
+        let mut s = U256::ZERO;
+        for (k, &balance) in balances.iter().enumerate() {
+            if k == j {
+                continue;
+            }
+            let x_k = if k == i { x } else { balance };
+            s += x_k;
+            c = self.mul_div(c, d, n_coins * x_k);
+        }
+        c = self.mul_div(c, d, ann * n_coins);
+        let b = s + d / ann;
 
-            U256::from(liquidity0.min(liquidity1))
+        let mut y = d;
+        for _ in 0..MAX_ITERATIONS {
+            let y_prev = y;
+            let numerator = y * y + c;
+            let denominator = U256::from(2) * y + b - d;
+            y = numerator / denominator;
+            if abs_diff_u256(y, y_prev) <= U256::from(1) {
+                break;
+            }
+        }
+
+        let old_balance_out = balances[j];
+        if old_balance_out > y + U256::from(1) {
+            old_balance_out - y - U256::from(1)
+        } else {
+            U256::ZERO
         }
     }
 }
@@ -270,49 +388,191 @@ impl<SDK: SharedAPI> MathematicalEngineAPI<SDK> for MathematicalEngine<SDK> {
 // ============ Advanced Mathematical Helper Functions ============
 
 impl<SDK: SharedAPI> MathematicalEngine<SDK> {
-    /// High-precision square root using Newton's method with fixed-point arithmetic
-    fn fixed_point_sqrt(&self, x: u128) -> u128 {
-        if x == 0 {
-            return 0;
+    /// Full-precision `floor(a * b / denominator)` with a 512-bit intermediate.
+    ///
+    /// Implements Remco Bloemen's algorithm (Uniswap v3 `FullMath.mulDiv`): the
+    /// 512-bit product is held as two 256-bit limbs and divided via the modular
+    /// inverse of the denominator, so realistic 18-decimal reserves no longer
+    /// overflow or lose precision through a `u128` downcast.
+    fn mul_div(&self, a: U256, b: U256, denominator: U256) -> U256 {
+        if denominator == U256::ZERO {
+            return U256::ZERO;
+        }
+
+        // 512-bit product [prod1 prod0] = a * b.
+        let mut prod0 = a.wrapping_mul(b);
+        let mm = a.mul_mod(b, U256::MAX);
+        let mut prod1 = mm
+            .wrapping_sub(prod0)
+            .wrapping_sub(if mm < prod0 { U256::from(1) } else { U256::ZERO });
+
+        if prod1 == U256::ZERO {
+            return prod0 / denominator;
+        }
+        // The true result must fit in 256 bits.
+        if denominator <= prod1 {
+            return U256::ZERO;
+        }
+
+        // Subtract the 256-bit remainder from the 512-bit product.
+        let remainder = a.mul_mod(b, denominator);
+        prod1 = prod1.wrapping_sub(if remainder > prod0 { U256::from(1) } else { U256::ZERO });
+        prod0 = prod0.wrapping_sub(remainder);
+
+        // Factor out the largest power of two and fold in the high limb.
+        let twos = denominator & denominator.wrapping_neg();
+        let denominator = denominator / twos;
+        prod0 /= twos;
+        let twos_inv = (U256::ZERO.wrapping_sub(twos) / twos).wrapping_add(U256::from(1));
+        prod0 |= prod1.wrapping_mul(twos_inv);
+
+        // Newton-Raphson inverse of the odd denominator mod 2^256.
+        let mut inv = U256::from(3).wrapping_mul(denominator) ^ U256::from(2);
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(U256::from(2).wrapping_sub(denominator.wrapping_mul(inv)));
+        }
+
+        prod0.wrapping_mul(inv)
+    }
+
+    /// Full-precision `ceil(a * b / denominator)`; rounds up when inexact.
+    #[allow(dead_code)]
+    fn mul_div_rounding_up(&self, a: U256, b: U256, denominator: U256) -> U256 {
+        let result = self.mul_div(a, b, denominator);
+        if denominator != U256::ZERO && a.mul_mod(b, denominator) > U256::ZERO {
+            result.wrapping_add(U256::from(1))
+        } else {
+            result
+        }
+    }
+
+    /// Solve the StableSwap invariant `D` by Newton's method, starting from
+    /// `D = Σx_i`. The `D^(n+1)` term is accumulated through [`Self::mul_div`]
+    /// so large balances do not overflow.
+    fn invariant_d(&self, balances: &[U256], amp: U256) -> U256 {
+        let n = balances.len();
+        if n == 0 {
+            return U256::ZERO;
         }
 
-        // Initial guess: use bit manipulation for good starting point
-        let mut z = x;
-        let mut y = (x + 1) / 2;
+        let n_coins = U256::from(n);
+        let mut sum = U256::ZERO;
+        for &x in balances {
+            if x == U256::ZERO {
+                return U256::ZERO;
+            }
+            sum += x;
+        }
+        if sum == U256::ZERO {
+            return U256::ZERO;
+        }
+
+        let ann = amp * n_coins.pow(n_coins);
+        let mut d = sum;
 
-        // Newton's method: y = (y + x/y) / 2
         for _ in 0..MAX_ITERATIONS {
-            if y >= z {
+            let mut d_p = d;
+            for &x in balances {
+                d_p = self.mul_div(d_p, d, n_coins * x);
+            }
+            let d_prev = d;
+            let numerator = ann * sum + d_p * n_coins;
+            let denominator = (ann - U256::from(1)) * d + (n_coins + U256::from(1)) * d_p;
+            d = self.mul_div(numerator, d, denominator);
+            if abs_diff_u256(d, d_prev) <= U256::from(1) {
                 break;
             }
-            z = y;
-            y = (x / y + y) / 2;
         }
 
-        z
+        d
     }
 
-    /// Calculate constant product output with high precision
+    /// Exact integer floor square root over the full `U256` range.
+    ///
+    /// The guess is seeded from the bit length (`2^ceil(bits/2)`), which is
+    /// always `>= sqrt(x)`, so Newton's method converges monotonically from
+    /// above in a handful of steps; a final clamp guarantees the exact floor
+    /// so large `k = reserve_in*reserve_out` products no longer saturate.
+    fn fixed_point_sqrt(&self, x: U256) -> U256 {
+        if x == U256::ZERO {
+            return U256::ZERO;
+        }
+        if x <= U256::from(3) {
+            return U256::from(1);
+        }
+
+        let bits = x.bit_len();
+        let mut result = U256::from(1) << bits.div_ceil(2);
+
+        // Newton's method: y = (y + x/y) / 2, halting once it stops decreasing.
+        for _ in 0..MAX_ITERATIONS {
+            let next = (result + x / result) / U256::from(2);
+            if next >= result {
+                break;
+            }
+            result = next;
+        }
+
+        // Correctness clamp; checked_mul guards the `result * result`
+        // comparison against overflow near the 128-bit boundary.
+        while checked_mul(result, result).map_or(true, |sq| sq > x) {
+            result -= U256::from(1);
+        }
+        loop {
+            let next = result + U256::from(1);
+            match checked_mul(next, next) {
+                Ok(sq) if sq <= x => result = next,
+                _ => break,
+            }
+        }
+        result
+    }
+
+    /// Liquidity-derived success factor in `[0, PRECISION_SCALE]`.
+    ///
+    /// `(reserveOut - amountOut) / (reserveOut + 1)` scaled to 1e6: a trade that
+    /// drains most of a pool scores near zero, while a small trade against deep
+    /// liquidity scores near one, mirroring the linear-success-probability
+    /// weighting used in payment routing.
+    fn success_factor(&self, reserve_out: U256, amount_out: U256) -> U256 {
+        if amount_out >= reserve_out {
+            return U256::ZERO;
+        }
+        let factor = self.mul_div(
+            reserve_out - amount_out,
+            U256::from(PRECISION_SCALE),
+            reserve_out + U256::from(1),
+        );
+        factor.min(U256::from(PRECISION_SCALE))
+    }
+
+    /// Calculate constant product output with full 256-bit precision
     fn calculate_constant_product_output(
         &self,
-        amount_in: u128,
-        reserve_in: u128,
-        reserve_out: u128,
-    ) -> u128 {
-        if amount_in == 0 || reserve_in == 0 || reserve_out == 0 {
-            return 0;
+        amount_in: U256,
+        reserve_in: U256,
+        reserve_out: U256,
+    ) -> U256 {
+        if amount_in == U256::ZERO || reserve_in == U256::ZERO || reserve_out == U256::ZERO {
+            return U256::ZERO;
         }
 
-        // Apply 0.3% fee (997/1000)
-        let amount_in_with_fee = amount_in * 997;
-        let numerator = amount_in_with_fee * reserve_out;
-        let denominator = (reserve_in * 1000) + amount_in_with_fee;
+        // Apply 0.3% fee (997/1000). The fee and reserve scaling are checked so
+        // an overflow surfaces as the zero sentinel rather than a wrapped value.
+        let amount_in_with_fee = match checked_mul(amount_in, U256::from(997)) {
+            Ok(v) => v,
+            Err(_) => return U256::ZERO,
+        };
+        let denominator = match checked_mul(reserve_in, U256::from(1000))
+            .and_then(|scaled| checked_add(scaled, amount_in_with_fee))
+        {
+            Ok(v) => v,
+            Err(_) => return U256::ZERO,
+        };
 
-        if denominator > 0 {
-            numerator / denominator
-        } else {
-            0
-        }
+        // mul_div folds reserve_out into the division so the numerator never
+        // overflows 256 bits.
+        self.mul_div(amount_in_with_fee, reserve_out, denominator)
     }
 
     /// Approximate exponential function using Taylor series with fixed-point arithmetic
@@ -356,7 +616,7 @@ impl<SDK: SharedAPI> MathematicalEngine<SDK> {
         }
 
         let ratio = (price_ratio_scaled * PRECISION_SCALE as u128) / initial_ratio_scaled;
-        let sqrt_ratio = self.fixed_point_sqrt(ratio);
+        let sqrt_ratio = self.fixed_point_sqrt(U256::from(ratio)).to::<u128>();
 
         // IL = 2 * sqrt(ratio) / (1 + ratio) - 1
         let numerator = 2 * sqrt_ratio;
@@ -369,6 +629,72 @@ impl<SDK: SharedAPI> MathematicalEngine<SDK> {
         }
     }
 
+    /// Core dynamic-fee computation shared by the equal-weighted and EWMA entry
+    /// points. All adjustments are in PRECISION_SCALE (1e6) fixed point.
+    fn dynamic_fee(&self, volume: u128, liquidity: u128, volatility: u128) -> U256 {
+        // Base fee: 30 basis points (0.3%)
+        let base_fee = 30u128;
+
+        // Volatility adjustment using Taylor series approximation of exponential
+        let volatility_multiplier =
+            self.approximate_exponential(volatility, PRECISION_SCALE as u128);
+
+        // Utilization ratio adjustment
+        let utilization_multiplier = if liquidity > 0 {
+            let ratio = (volume * PRECISION_SCALE as u128) / liquidity;
+            // Cap utilization impact at 2x
+            1_000_000 + (ratio / 2).min(1_000_000)
+        } else {
+            1_000_000 // 1.0 in fixed point
+        };
+
+        // Calculate dynamic fee with both adjustments
+        let dynamic_fee = (base_fee * volatility_multiplier * utilization_multiplier)
+            / (PRECISION_SCALE as u128 * PRECISION_SCALE as u128);
+
+        // Cap at 100 basis points (1%)
+        U256::from(dynamic_fee.min(100))
+    }
+
+    /// Exponentially-weighted (RiskMetrics) volatility over the log-returns of
+    /// `price_history`, with decay `lambda_scaled` in PRECISION_SCALE (1e6); the
+    /// RiskMetrics default is `lambda ≈ 0.94`.
+    ///
+    /// Recursively updates `var_t = lambda·var_{t-1} + (1 - lambda)·r_t²`,
+    /// seeding `var_0` with the first squared return, then annualizes via
+    /// `sqrt(var · 365)` — the same path as [`Self::calculate_volatility`].
+    pub fn calculate_ewma_volatility(&self, price_history: &[U256], lambda_scaled: u128) -> u128 {
+        if price_history.len() < 2 {
+            return 0;
+        }
+
+        let scale = PRECISION_SCALE as u128;
+        let prices: Vec<u128> = price_history.iter().map(|p| p.to::<u128>()).collect();
+
+        // Log-returns using the same approximation as the equal-weighted path.
+        let mut returns = Vec::new();
+        for i in 1..prices.len() {
+            if prices[i - 1] > 0 {
+                let ratio = (prices[i] * scale) / prices[i - 1];
+                returns.push(self.approximate_logarithm(ratio, scale));
+            }
+        }
+        if returns.is_empty() {
+            return 0;
+        }
+
+        // Seed with the first squared return, then apply the EWMA recursion.
+        let squared = |r: u128| (r * r) / scale;
+        let mut var = squared(returns[0]);
+        let one_minus_lambda = scale.saturating_sub(lambda_scaled);
+        for &r in returns.iter().skip(1) {
+            var = (lambda_scaled * var + one_minus_lambda * squared(r)) / scale;
+        }
+
+        // Return annualized volatility (assuming daily data).
+        self.fixed_point_sqrt(U256::from(var * 365)).to::<u128>() * 100 / scale
+    }
+
     /// Advanced volatility calculation using statistical methods with fixed-point math
     pub fn calculate_volatility(&self, price_history: &[U256], window: usize) -> u128 {
         if price_history.len() < window || window < 2 {
@@ -413,7 +739,7 @@ impl<SDK: SharedAPI> MathematicalEngine<SDK> {
         let variance = variance_sum / (returns.len() - 1) as u128;
 
         // Return annualized volatility (assuming daily data)
-        self.fixed_point_sqrt(variance * 365) * 100 / PRECISION_SCALE as u128
+        self.fixed_point_sqrt(U256::from(variance * 365)).to::<u128>() * 100 / PRECISION_SCALE as u128
     }
 
     /// Approximate natural logarithm using Taylor series
@@ -449,6 +775,37 @@ impl<SDK: SharedAPI> MathematicalEngine<SDK> {
     }
 }
 
+/// Relax the best entry for `token`, keeping the highest-scoring path reaching
+/// it. Inserts a new entry when the token has not been seen before.
+fn relax_best(
+    best: &mut Vec<(Address, U256, U256, Vec<u32>)>,
+    token: Address,
+    amount: U256,
+    score: U256,
+    path: Vec<u32>,
+) {
+    for entry in best.iter_mut() {
+        if entry.0 == token {
+            if score > entry.2 {
+                entry.1 = amount;
+                entry.2 = score;
+                entry.3 = path;
+            }
+            return;
+        }
+    }
+    best.push((token, amount, score, path));
+}
+
+/// Absolute difference `|a - b|` for unsigned 256-bit values.
+fn abs_diff_u256(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
 // ============ Entry Point ============
 
 impl<SDK: SharedAPI> MathematicalEngine<SDK> {
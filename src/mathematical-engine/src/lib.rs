@@ -44,6 +44,91 @@ pub struct Pool {
     pub reserve_out: U256,
 }
 
+/// Parameters for a Curve-style StableSwap pool of correlated assets
+#[derive(Codec, Debug, Default, Clone, PartialEq)]
+pub struct StableSwapParams {
+    pub balances: Vec<U256>, // current pool balances, one per coin
+    pub amp: U256,           // amplification coefficient A
+}
+
+/// A Balancer-style weighted pool with arbitrary token weights
+#[derive(Codec, Debug, Default, Clone, PartialEq)]
+pub struct WeightedPool {
+    pub balance_in: U256,
+    pub weight_in: U256,
+    pub balance_out: U256,
+    pub weight_out: U256,
+}
+
+/// Signed fixed-point number: a `U256` magnitude plus a sign, at the 1e18 scale.
+///
+/// Used where a result can legitimately go below zero — `ln(x)` for `x < 1`, or
+/// the (always-negative) impermanent loss, whose magnitude is stored in basis
+/// points rather than at the 1e18 scale — which a bare `U256` can only
+/// represent by underflowing or clamping to zero. The semantics mirror a
+/// magnitude/sign fixed-point number; zero is always stored non-negative.
+#[derive(Codec, Debug, Default, Clone, PartialEq)]
+pub struct I256Fixed {
+    pub value: U256,
+    pub negative: bool,
+}
+
+impl I256Fixed {
+    /// Build from a signed magnitude, normalising the sign of zero.
+    fn from_parts(value: U256, negative: bool) -> Self {
+        Self {
+            value,
+            negative: negative && value != U256::ZERO,
+        }
+    }
+
+    /// Checked constructor from the difference `a - b`, negative when `b > a`.
+    fn from_diff(a: U256, b: U256) -> Self {
+        if a >= b {
+            Self::from_parts(a - b, false)
+        } else {
+            Self::from_parts(b - a, true)
+        }
+    }
+
+    /// Arithmetic negation.
+    fn neg(self) -> Self {
+        Self::from_parts(self.value, !self.negative)
+    }
+
+    /// Signed addition.
+    fn add(self, rhs: Self) -> Self {
+        if self.negative == rhs.negative {
+            Self::from_parts(self.value + rhs.value, self.negative)
+        } else if self.value >= rhs.value {
+            Self::from_parts(self.value - rhs.value, self.negative)
+        } else {
+            Self::from_parts(rhs.value - self.value, rhs.negative)
+        }
+    }
+
+    /// Signed subtraction.
+    fn sub(self, rhs: Self) -> Self {
+        self.add(rhs.neg())
+    }
+
+    /// Signed fixed-point multiplication (1e18 scale).
+    fn mul(self, rhs: Self) -> Self {
+        Self::from_parts(
+            mul_div(self.value, rhs.value, SCALE_18),
+            self.negative ^ rhs.negative,
+        )
+    }
+
+    /// Signed fixed-point division (1e18 scale).
+    fn div(self, rhs: Self) -> Self {
+        Self::from_parts(
+            mul_div(self.value, SCALE_18, rhs.value),
+            self.negative ^ rhs.negative,
+        )
+    }
+}
+
 // ============ Fixed-Point Arithmetic Constants ============
 
 // Scaling factors for fixed-point arithmetic
@@ -58,6 +143,43 @@ const LN2_SCALED: U256 = U256::from_limbs([693_147_180_559_945_309, 0, 0, 0]); /
 // Maximum iterations for convergence algorithms
 const MAX_ITERATIONS: u32 = 20;
 
+// ============ Checked Arithmetic Layer ============
+
+/// Arithmetic error surfaced by the checked-math layer.
+///
+/// Raw `*`/`+`/`pow` wrap silently in WASM release builds (and panic in debug),
+/// so the constant-product, swap-optimisation, LP-token, impermanent-loss,
+/// routing and `exp_fixed` steps are funnelled through the `checked_*` helpers,
+/// which report `Overflow` instead of returning a corrupted number. The
+/// StableSwap, weighted-pool, logarithm and `I256Fixed` arithmetic use raw
+/// operators, and no helper produces `DivByZero` or `ConvergenceFailure`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MathError {
+    Overflow,
+    DivByZero,
+    ConvergenceFailure,
+}
+
+/// Checked addition that reports overflow instead of wrapping.
+fn checked_add(a: U256, b: U256) -> Result<U256, MathError> {
+    a.checked_add(b).ok_or(MathError::Overflow)
+}
+
+/// Checked multiplication that reports overflow instead of wrapping.
+fn checked_mul(a: U256, b: U256) -> Result<U256, MathError> {
+    a.checked_mul(b).ok_or(MathError::Overflow)
+}
+
+/// Unwrap a checked-math result, reverting the call on error.
+/// In WASM a panic traps and surfaces to the ABI caller as a revert, so callers
+/// get deterministic failure rather than a wrong number.
+fn unwrap_or_revert(result: Result<U256, MathError>) -> U256 {
+    match result {
+        Ok(value) => value,
+        Err(_) => panic!("mathematical engine: arithmetic error"),
+    }
+}
+
 // ============ Contract Implementation ============
 
 #[derive(Contract)]
@@ -78,8 +200,12 @@ pub trait MathematicalEngineAPI {
         fee_rate: U256,
     ) -> OptimizationResult;
     fn calculate_lp_tokens(&self, amount0: U256, amount1: U256) -> U256;
-    fn calculate_impermanent_loss(&self, initial_price: U256, current_price: U256) -> U256;
+    fn calculate_impermanent_loss(&self, initial_price: U256, current_price: U256) -> I256Fixed;
     fn find_optimal_route(&self, amount_in: U256, pools: Vec<Pool>, fee_rates: Vec<U256>) -> U256;
+    fn calculate_stableswap_d(&self, params: StableSwapParams) -> U256;
+    fn get_stableswap_dy(&self, params: StableSwapParams, i: U256, j: U256, dx: U256) -> U256;
+    fn calculate_stableswap_lp_tokens(&self, params: StableSwapParams) -> U256;
+    fn calculate_weighted_out(&self, pool: WeightedPool, amount_in: U256, fee_rate: U256) -> U256;
 }
 
 #[router(mode = "solidity")]
@@ -99,13 +225,16 @@ impl<SDK: SharedAPI> MathematicalEngineAPI for MathematicalEngine<SDK> {
         // amountOut = (amountIn * feeMultiplier * reserveOut) / (reserveIn * 10000 + amountIn * feeMultiplier)
 
         let fee_multiplier = BASIS_POINTS - params.fee_rate;
-        let amount_in_with_fee = params.amount_in * fee_multiplier;
+        let amount_in_with_fee = unwrap_or_revert(checked_mul(params.amount_in, fee_multiplier));
 
-        // Use mul_div to prevent overflow and maintain precision
-        let numerator = amount_in_with_fee * params.reserve_out;
-        let denominator = params.reserve_in * BASIS_POINTS + amount_in_with_fee;
+        // Fold reserve_out into mul_div so the numerator never overflows, and
+        // guard the denominator sum against overflow.
+        let denominator = unwrap_or_revert(checked_add(
+            unwrap_or_revert(checked_mul(params.reserve_in, BASIS_POINTS)),
+            amount_in_with_fee,
+        ));
 
-        mul_div(numerator, U256::from(1), denominator)
+        mul_div(amount_in_with_fee, params.reserve_out, denominator)
     }
 
     /// Calculate dynamic fees based on market conditions
@@ -135,9 +264,15 @@ impl<SDK: SharedAPI> MathematicalEngineAPI for MathematicalEngine<SDK> {
         if params.volume_24h > SCALE_18 {
             // More than 1 ETH volume
             // Calculate log-based discount
-            let log_volume = ln_fixed(params.volume_24h / SCALE_18);
+            // ln() is 1e18-scaled, so feed the 1e18-scaled volume directly;
+            // above 1 ETH the logarithm is positive, giving a discount.
+            let log_volume = ln_fixed(params.volume_24h);
             // Scale to basis points (max 10 bp discount)
-            let volume_discount = mul_div(log_volume, U256::from(2), SCALE_18).min(U256::from(10));
+            let volume_discount = if log_volume.negative {
+                U256::ZERO
+            } else {
+                mul_div(log_volume.value, U256::from(2), SCALE_18).min(U256::from(10))
+            };
             fee = fee.saturating_sub(volume_discount);
         }
 
@@ -164,19 +299,22 @@ impl<SDK: SharedAPI> MathematicalEngineAPI for MathematicalEngine<SDK> {
         // For constant product AMM: find optimal split to minimize price impact
         // Optimal amount = sqrt(k * total_amount * fee_multiplier)
 
-        let k = reserve_in * reserve_out;
+        let k = unwrap_or_revert(checked_mul(reserve_in, reserve_out));
         let fee_multiplier = BASIS_POINTS - fee_rate;
 
         // Calculate using fixed-point square root
-        let inner = mul_div(k, total_amount * fee_multiplier, BASIS_POINTS);
+        let total_with_fee = unwrap_or_revert(checked_mul(total_amount, fee_multiplier));
+        let inner = mul_div(k, total_with_fee, BASIS_POINTS);
         let optimal_ratio = sqrt_fixed(inner);
         let optimal_amount = optimal_ratio.min(total_amount);
 
         // Calculate expected output with high precision
-        let amount_in_with_fee = optimal_amount * fee_multiplier;
-        let numerator = amount_in_with_fee * reserve_out;
-        let denominator = reserve_in * BASIS_POINTS + amount_in_with_fee;
-        let expected_output = mul_div(numerator, U256::from(1), denominator);
+        let amount_in_with_fee = unwrap_or_revert(checked_mul(optimal_amount, fee_multiplier));
+        let denominator = unwrap_or_revert(checked_add(
+            unwrap_or_revert(checked_mul(reserve_in, BASIS_POINTS)),
+            amount_in_with_fee,
+        ));
+        let expected_output = mul_div(amount_in_with_fee, reserve_out, denominator);
 
         // Calculate price impact in basis points
         // spot_price = reserve_out / reserve_in
@@ -205,16 +343,17 @@ impl<SDK: SharedAPI> MathematicalEngineAPI for MathematicalEngine<SDK> {
     fn calculate_lp_tokens(&self, amount0: U256, amount1: U256) -> U256 {
         // Geometric mean: sqrt(amount0 * amount1)
         // Using high-precision fixed-point sqrt
-        let product = amount0 * amount1;
+        let product = unwrap_or_revert(checked_mul(amount0, amount1));
         sqrt_fixed(product)
     }
 
     /// Calculate impermanent loss with high precision
     /// Returns loss in basis points
     #[function_id("calculateImpermanentLoss(uint256,uint256)")]
-    fn calculate_impermanent_loss(&self, initial_price: U256, current_price: U256) -> U256 {
+    fn calculate_impermanent_loss(&self, initial_price: U256, current_price: U256) -> I256Fixed {
         if initial_price == U256::ZERO || current_price == U256::ZERO {
-            return U256::ZERO;
+            // Degenerate input - a true zero, distinct from a negative loss.
+            return I256Fixed::default();
         }
 
         // IL = 2 * sqrt(price_ratio) / (1 + price_ratio) - 1
@@ -227,16 +366,16 @@ impl<SDK: SharedAPI> MathematicalEngineAPI for MathematicalEngine<SDK> {
         let sqrt_ratio = sqrt_fixed(price_ratio);
 
         // Calculate IL: 2 * sqrt_ratio / (1 + price_ratio) - 1
-        let numerator = U256::from(2) * sqrt_ratio;
-        let denominator = SCALE_18 + price_ratio;
+        let numerator = unwrap_or_revert(checked_mul(U256::from(2), sqrt_ratio));
+        let denominator = unwrap_or_revert(checked_add(SCALE_18, price_ratio));
         let il_factor = mul_div(numerator, SCALE_18, denominator);
 
         if il_factor < SCALE_18 {
-            // Loss scenario - convert to basis points
+            // Loss scenario - a negative magnitude in basis points.
             let loss = SCALE_18 - il_factor;
-            mul_div(loss, BASIS_POINTS, SCALE_18)
+            I256Fixed::from_parts(mul_div(loss, BASIS_POINTS, SCALE_18), true)
         } else {
-            U256::ZERO
+            I256Fixed::default()
         }
     }
 
@@ -256,13 +395,15 @@ impl<SDK: SharedAPI> MathematicalEngineAPI for MathematicalEngine<SDK> {
             let fee_rate = fee_rates[i];
 
             let fee_multiplier = BASIS_POINTS - fee_rate;
-            let amount_in_with_fee = current_amount * fee_multiplier;
+            let amount_in_with_fee = unwrap_or_revert(checked_mul(current_amount, fee_multiplier));
 
             // High-precision calculation using mul_div
-            let numerator = amount_in_with_fee * pool.reserve_out;
-            let denominator = pool.reserve_in * BASIS_POINTS + amount_in_with_fee;
+            let denominator = unwrap_or_revert(checked_add(
+                unwrap_or_revert(checked_mul(pool.reserve_in, BASIS_POINTS)),
+                amount_in_with_fee,
+            ));
 
-            current_amount = mul_div(numerator, U256::from(1), denominator);
+            current_amount = mul_div(amount_in_with_fee, pool.reserve_out, denominator);
 
             if current_amount == U256::ZERO {
                 break;
@@ -271,6 +412,171 @@ impl<SDK: SharedAPI> MathematicalEngineAPI for MathematicalEngine<SDK> {
 
         current_amount
     }
+
+    /// Compute the StableSwap invariant `D` for the pool balances.
+    /// `D` is the constant-sum equivalent of the constant-product `k` and is
+    /// also the amount of LP tokens backing the pool at its current balances.
+    #[function_id("calculateStableswapD((uint256[],uint256))")]
+    fn calculate_stableswap_d(&self, params: StableSwapParams) -> U256 {
+        stableswap_d(&params.balances, params.amp)
+    }
+
+    /// Price a swap of `dx` of coin `i` into coin `j`, returning `dy`.
+    /// The invariant is held fixed while solving for the new balance of coin
+    /// `j`; the `- 1` rounds the output in the pool's favour.
+    #[function_id("getStableswapDy((uint256[],uint256),uint256,uint256,uint256)")]
+    fn get_stableswap_dy(&self, params: StableSwapParams, i: U256, j: U256, dx: U256) -> U256 {
+        let n = params.balances.len();
+        let i = i.to::<usize>();
+        let j = j.to::<usize>();
+        if i >= n || j >= n || i == j {
+            return U256::ZERO;
+        }
+
+        let d = stableswap_d(&params.balances, params.amp);
+        if d == U256::ZERO {
+            return U256::ZERO;
+        }
+
+        let x = params.balances[i] + dx;
+        let y = stableswap_y(&params.balances, params.amp, i, j, x, d);
+
+        let old = params.balances[j];
+        if old > y + U256::from(1) {
+            old - y - U256::from(1)
+        } else {
+            U256::ZERO
+        }
+    }
+
+    /// LP tokens minted for an initial deposit of `balances`.
+    /// Curve mints the invariant `D` as the pool's first liquidity.
+    #[function_id("calculateStableswapLpTokens((uint256[],uint256))")]
+    fn calculate_stableswap_lp_tokens(&self, params: StableSwapParams) -> U256 {
+        stableswap_d(&params.balances, params.amp)
+    }
+
+    /// Price a swap through a Balancer-style weighted pool:
+    /// `amountOut = balanceOut · (1 − (balanceIn / (balanceIn + amountIn·(1−fee)))^(weightIn/weightOut))`.
+    #[function_id("calculateWeightedOut((uint256,uint256,uint256,uint256),uint256,uint256)")]
+    fn calculate_weighted_out(&self, pool: WeightedPool, amount_in: U256, fee_rate: U256) -> U256 {
+        weighted_out(&pool, amount_in, fee_rate)
+    }
+}
+
+/// Balancer weighted-pool output amount (see [`MathematicalEngineAPI::calculate_weighted_out`]).
+fn weighted_out(pool: &WeightedPool, amount_in: U256, fee_rate: U256) -> U256 {
+    if pool.balance_in == U256::ZERO || pool.weight_out == U256::ZERO {
+        return U256::ZERO;
+    }
+
+    let fee_multiplier = BASIS_POINTS - fee_rate;
+    let amount_in_after_fee = mul_div(amount_in, fee_multiplier, BASIS_POINTS);
+
+    // base = balanceIn / (balanceIn + amountIn·(1−fee)), always <= 1.
+    let base = mul_div(
+        pool.balance_in,
+        SCALE_18,
+        pool.balance_in + amount_in_after_fee,
+    );
+    // exponent = weightIn / weightOut.
+    let exponent = mul_div(pool.weight_in, SCALE_18, pool.weight_out);
+
+    let power = pow_fixed(base, exponent);
+    if power >= SCALE_18 {
+        return U256::ZERO;
+    }
+    mul_div(pool.balance_out, SCALE_18 - power, SCALE_18)
+}
+
+// ============ StableSwap Invariant Functions ============
+
+/// Solve the StableSwap invariant `D` by Newton's method.
+///
+/// `A·n^n·Σx_i + D = A·D·n^n + D^(n+1)/(n^n·Πx_i)`, iterated from `D = Σx_i`.
+/// The `D^(n+1)` term is accumulated through [`mul_div`] so it never overflows.
+fn stableswap_d(balances: &[U256], amp: U256) -> U256 {
+    let n = balances.len();
+    if n == 0 {
+        return U256::ZERO;
+    }
+
+    let n_coins = U256::from(n);
+    let mut sum = U256::ZERO;
+    for &x in balances {
+        if x == U256::ZERO {
+            return U256::ZERO;
+        }
+        sum = sum + x;
+    }
+    if sum == U256::ZERO {
+        return U256::ZERO;
+    }
+
+    let ann = amp * n_coins.pow(n_coins);
+    let mut d = sum;
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for &x in balances {
+            d_p = mul_div(d_p, d, n_coins * x);
+        }
+        let d_prev = d;
+        let numerator = ann * sum + d_p * n_coins;
+        let denominator = (ann - U256::from(1)) * d + (n_coins + U256::from(1)) * d_p;
+        d = mul_div(numerator, d, denominator);
+
+        if abs_diff(d, d_prev) <= U256::from(1) {
+            break;
+        }
+    }
+
+    d
+}
+
+/// Solve for the new balance of coin `j` that keeps the invariant `D` fixed
+/// after coin `i`'s balance is set to `x`, via the quadratic-Newton recurrence
+/// `y = (y² + c) / (2y + b − D)`.
+fn stableswap_y(balances: &[U256], amp: U256, i: usize, j: usize, x: U256, d: U256) -> U256 {
+    let n = balances.len();
+    let n_coins = U256::from(n);
+    let ann = amp * n_coins.pow(n_coins);
+
+    let mut c = d;
+    let mut s = U256::ZERO;
+    for k in 0..n {
+        if k == j {
+            continue;
+        }
+        let x_k = if k == i { x } else { balances[k] };
+        s = s + x_k;
+        c = mul_div(c, d, n_coins * x_k);
+    }
+    c = mul_div(c, d, ann * n_coins);
+    let b = s + d / ann;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_prev = y;
+        let numerator = y * y + c;
+        let denominator = U256::from(2) * y + b - d;
+        y = numerator / denominator;
+
+        if abs_diff(y, y_prev) <= U256::from(1) {
+            break;
+        }
+    }
+
+    y
+}
+
+/// Absolute difference `|a - b|` for unsigned values.
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
 }
 
 // ============ Fixed-Point Mathematical Functions ============
@@ -285,35 +591,36 @@ fn sqrt_fixed(x: U256) -> U256 {
         return U256::from(1);
     }
 
-    // Initial guess: find the highest set bit and use 2^(bit_position/2)
-    let mut z = x;
-    let mut y = x;
-
-    // Optimize initial guess using bit manipulation
-    if x >= U256::from(2).pow(U256::from(128)) {
-        y = U256::from(2).pow(U256::from(128));
-    } else if x >= U256::from(2).pow(U256::from(64)) {
-        y = U256::from(2).pow(U256::from(64));
-    } else if x >= U256::from(2).pow(U256::from(32)) {
-        y = U256::from(2).pow(U256::from(32));
-    } else if x >= U256::from(2).pow(U256::from(16)) {
-        y = U256::from(2).pow(U256::from(16));
-    } else {
-        y = x / U256::from(2);
-    }
+    // Seed the guess from the exact bit length: 2^ceil(bits/2) is always >=
+    // sqrt(x), so Newton-Raphson converges monotonically from above in a
+    // handful of steps for any 256-bit input.
+    let bits = x.bit_len();
+    let mut result = U256::from(1) << bits.div_ceil(2);
 
-    // Newton-Raphson iteration: y = (y + x/y) / 2
+    // Newton-Raphson iteration: y = (y + x/y) / 2, halting once it stops
+    // decreasing.
     for _ in 0..MAX_ITERATIONS {
-        z = y;
-        y = (y + x / y) / U256::from(2);
-
-        // Check for convergence
-        if y >= z {
-            return z;
+        let next = (result + x / result) / U256::from(2);
+        if next >= result {
+            break;
         }
+        result = next;
     }
 
-    z
+    // Final correctness clamp so the result is the exact integer floor of the
+    // square root. checked_mul guards the `result * result` comparison against
+    // overflow near the 128-bit boundary.
+    while checked_mul(result, result).map_or(true, |sq| sq > x) {
+        result -= U256::from(1);
+    }
+    loop {
+        let next = result + U256::from(1);
+        match checked_mul(next, next) {
+            Ok(sq) if sq <= x => result = next,
+            _ => break,
+        }
+    }
+    result
 }
 
 /// Approximated exponential function using Taylor series
@@ -333,7 +640,7 @@ fn exp_fixed(x: U256) -> U256 {
     // Calculate up to 6 terms for reasonable precision
     for i in 1..7 {
         term = mul_div(term, x_clamped, U256::from(i) * SCALE_18);
-        result = result + term;
+        result = unwrap_or_revert(checked_add(result, term));
 
         // Early exit if term becomes negligible
         if term < U256::from(1000) {
@@ -345,17 +652,56 @@ fn exp_fixed(x: U256) -> U256 {
     result
 }
 
-/// Approximated natural logarithm using series expansion
-/// For x close to 1: ln(x) ≈ (x-1) - (x-1)²/2 + (x-1)³/3 - ...
-/// Input and output scaled by 1e18
-fn ln_fixed(x: U256) -> U256 {
-    if x == U256::ZERO {
-        return U256::ZERO; // Technically undefined, but return 0 for safety
+/// Fixed-point power `base^exp` for 1e18-scaled operands, via `exp(exp·ln(base))`.
+///
+/// Only the magnitude of `ln_fixed(base)` is used and `exp_fixed` takes a
+/// non-negative argument, so for `base < 1` this uses `base^e = 1 / (1/base)^e`,
+/// keeping the logarithm argument at or above parity.
+fn pow_fixed(base: U256, exp: U256) -> U256 {
+    if exp == U256::ZERO || base == SCALE_18 {
+        return SCALE_18; // x^0 = 1, 1^x = 1
     }
-    if x == SCALE_18 {
-        return U256::ZERO; // ln(1) = 0
+    if base == U256::ZERO {
+        return U256::ZERO;
+    }
+    if exp == SCALE_18 {
+        return base; // x^1 = x
     }
 
+    if base < SCALE_18 {
+        // Reflect through 1 so the logarithm stays above parity.
+        let inv_base = mul_div(SCALE_18, SCALE_18, base);
+        let p = pow_fixed(inv_base, exp);
+        if p == U256::ZERO {
+            return U256::ZERO;
+        }
+        return mul_div(SCALE_18, SCALE_18, p);
+    }
+
+    // base >= 1 here, so the logarithm is non-negative.
+    let ln_base = ln_fixed(base).value;
+    let e = mul_div(exp, ln_base, SCALE_18);
+    exp_fixed(e)
+}
+
+/// Approximated natural logarithm using series expansion.
+/// For `x > 1` the result is positive; for `x < 1` it is negative, computed as
+/// `ln(x) = -ln(1/x)` so the series argument never underflows below parity.
+/// Input and output scaled by 1e18.
+fn ln_fixed(x: U256) -> I256Fixed {
+    if x == U256::ZERO || x == SCALE_18 {
+        // ln(0) is undefined (return 0 for safety); ln(1) = 0.
+        return I256Fixed::default();
+    }
+    if x < SCALE_18 {
+        let inv = mul_div(SCALE_18, SCALE_18, x);
+        return I256Fixed::from_parts(ln_fixed_pos(inv), true);
+    }
+    I256Fixed::from_parts(ln_fixed_pos(x), false)
+}
+
+/// Natural logarithm for `x >= 1e18`, returning the positive magnitude.
+fn ln_fixed_pos(x: U256) -> U256 {
     // Use properties of logarithm to keep x close to 1
     // ln(x) = ln(x/2^n) + n*ln(2)
     let mut result = U256::ZERO;
@@ -391,22 +737,72 @@ fn ln_fixed(x: U256) -> U256 {
     result + series_sum
 }
 
-/// Safe multiplication and division with overflow protection
-/// Returns (a * b) / c without intermediate overflow
-fn mul_div(a: U256, b: U256, c: U256) -> U256 {
-    if c == U256::ZERO {
+/// Full-precision `floor(a * b / denominator)` with a 512-bit intermediate.
+///
+/// Computing `a * b` directly overflows whenever the product exceeds 256 bits,
+/// which silently corrupts the slippage, optimization and routing math for
+/// large reserves. This follows Remco Bloemen's algorithm (Uniswap v3
+/// `FullMath.mulDiv`): the product is held as two 256-bit limbs and the final
+/// division is performed via the modular inverse of the denominator, so no
+/// intermediate ever exceeds 256 bits.
+fn mul_div(a: U256, b: U256, denominator: U256) -> U256 {
+    if denominator == U256::ZERO {
         return U256::ZERO; // Avoid division by zero
     }
 
-    // Check if we can do simple calculation without overflow risk
-    if a == U256::ZERO || b == U256::ZERO {
+    // 512-bit product [prod1 prod0] = a * b, split into high and low limbs.
+    let mut prod0 = a.wrapping_mul(b); // low 256 bits
+    let mm = a.mul_mod(b, U256::MAX);
+    let mut prod1 = mm
+        .wrapping_sub(prod0)
+        .wrapping_sub(if mm < prod0 { U256::from(1) } else { U256::ZERO }); // high 256 bits
+
+    // Short-circuit when the product already fits in 256 bits.
+    if prod1 == U256::ZERO {
+        return prod0 / denominator;
+    }
+
+    // The true result must fit in 256 bits, otherwise it would overflow.
+    if denominator <= prod1 {
         return U256::ZERO;
     }
 
-    // Perform the calculation
-    // U256 in Fluentbase should handle this correctly
-    let product = a * b;
-    product / c
+    // Subtract the 256-bit remainder from the 512-bit product with borrow.
+    let remainder = a.mul_mod(b, denominator);
+    prod1 = prod1.wrapping_sub(if remainder > prod0 { U256::from(1) } else { U256::ZERO });
+    prod0 = prod0.wrapping_sub(remainder);
+
+    // Factor the largest power of two out of the denominator.
+    let twos = denominator & denominator.wrapping_neg();
+    let denominator = denominator / twos;
+    prod0 /= twos;
+
+    // Fold the high limb into prod0 by shifting it down by the same power of two.
+    let twos_inv = (U256::ZERO.wrapping_sub(twos) / twos).wrapping_add(U256::from(1));
+    prod0 |= prod1.wrapping_mul(twos_inv);
+
+    // Invert the (now odd) denominator modulo 2^256 by Newton-Raphson, which
+    // doubles the number of correct bits each step (4 -> 8 -> ... -> 256).
+    let mut inv = U256::from(3).wrapping_mul(denominator) ^ U256::from(2);
+    for _ in 0..6 {
+        inv = inv.wrapping_mul(U256::from(2).wrapping_sub(denominator.wrapping_mul(inv)));
+    }
+
+    prod0.wrapping_mul(inv)
+}
+
+/// Full-precision `ceil(a * b / denominator)`.
+///
+/// Identical to [`mul_div`] but rounds up when the division is inexact, which
+/// matters for amounts that must round in the pool's favour.
+#[allow(dead_code)]
+fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> U256 {
+    let result = mul_div(a, b, denominator);
+    if denominator != U256::ZERO && a.mul_mod(b, denominator) > U256::ZERO {
+        result.wrapping_add(U256::from(1))
+    } else {
+        result
+    }
 }
 
 impl<SDK: SharedAPI> MathematicalEngine<SDK> {
@@ -434,6 +830,24 @@ mod tests {
         assert_eq!(sqrt_fixed(U256::from(1000000)), U256::from(1000));
     }
 
+    #[test]
+    fn test_sqrt_exact_floor_full_range() {
+        // floor(sqrt(2^256 - 1)) = 2^128 - 1
+        let max_root = (U256::from(1) << 128) - U256::from(1);
+        assert_eq!(sqrt_fixed(U256::MAX), max_root);
+
+        // Perfect square straddling the 128-bit boundary.
+        let r = U256::from(1) << 64; // 2^64
+        let square = r * r; // 2^128
+        assert_eq!(sqrt_fixed(square), r);
+
+        // Just above a perfect square still floors to the same root.
+        assert_eq!(sqrt_fixed(square + U256::from(1)), r);
+
+        // Just below a perfect square floors to root - 1.
+        assert_eq!(sqrt_fixed(square - U256::from(1)), r - U256::from(1));
+    }
+
     #[test]
     fn test_mul_div() {
         // Test basic multiplication and division
@@ -451,6 +865,166 @@ mod tests {
         assert_eq!(result, U256::from(2));
     }
 
+    #[test]
+    fn test_mul_div_full_precision() {
+        // Product exceeds 256 bits but the quotient fits: a = b = 2^200,
+        // denominator = 2^256 - 1, so floor(2^400 / (2^256 - 1)) = 2^144.
+        let two_200 = U256::from(1) << 200;
+        let quotient = mul_div(two_200, two_200, U256::MAX);
+        assert_eq!(quotient, U256::from(1) << 144);
+
+        // Dividing the full product back by one operand recovers the other.
+        let max = U256::MAX;
+        assert_eq!(mul_div(max, SCALE_18, max), SCALE_18);
+        assert_eq!(mul_div(max, max, max), max);
+
+        // Result that would overflow 256 bits returns zero rather than wrapping.
+        assert_eq!(mul_div(max, U256::from(2), U256::from(1)), U256::ZERO);
+    }
+
+    #[test]
+    fn test_mul_div_rounding_up() {
+        // Exact division is unaffected by rounding.
+        assert_eq!(
+            mul_div_rounding_up(U256::from(6), U256::from(2), U256::from(3)),
+            U256::from(4)
+        );
+        // Inexact division rounds up.
+        assert_eq!(
+            mul_div_rounding_up(U256::from(7), U256::from(1), U256::from(3)),
+            U256::from(3)
+        );
+        assert_eq!(
+            mul_div(U256::from(7), U256::from(1), U256::from(3)),
+            U256::from(2)
+        );
+    }
+
+    #[test]
+    fn test_stableswap_d_balanced() {
+        // A perfectly balanced pool has D = n * x exactly.
+        let balances = alloc::vec![U256::from(1000), U256::from(1000)];
+        assert_eq!(stableswap_d(&balances, U256::from(100)), U256::from(2000));
+
+        let balances3 = alloc::vec![U256::from(500), U256::from(500), U256::from(500)];
+        assert_eq!(stableswap_d(&balances3, U256::from(100)), U256::from(1500));
+    }
+
+    #[test]
+    fn test_stableswap_dy_low_slippage() {
+        // Swapping within a deep, amplified pool returns nearly 1:1.
+        let params = StableSwapParams {
+            balances: alloc::vec![U256::from(1_000_000), U256::from(1_000_000)],
+            amp: U256::from(100),
+        };
+        let d = stableswap_d(&params.balances, params.amp);
+        let y = stableswap_y(
+            &params.balances,
+            params.amp,
+            0,
+            1,
+            params.balances[0] + U256::from(1000),
+            d,
+        );
+        let dy = params.balances[1] - y - U256::from(1);
+        assert!(dy > U256::from(990) && dy < U256::from(1000));
+    }
+
+    #[test]
+    fn test_weighted_out_matches_constant_product_for_equal_weights() {
+        // With equal weights the exponent is 1, so the weighted formula
+        // reduces to the constant-product output (no fee here).
+        let pool = WeightedPool {
+            balance_in: U256::from(1000) * SCALE_18,
+            weight_in: SCALE_18,
+            balance_out: U256::from(1000) * SCALE_18,
+            weight_out: SCALE_18,
+        };
+        let out = weighted_out(&pool, U256::from(100) * SCALE_18, U256::ZERO);
+        // 1000e18 * 100 / 1100 ≈ 90.909e18
+        assert!(out > U256::from(90) * SCALE_18 && out < U256::from(91) * SCALE_18);
+    }
+
+    #[test]
+    fn test_weighted_out_heavier_in_weight() {
+        // An 80/20 pool prices a swap into the minority token; output is
+        // positive and strictly below the output reserve.
+        let pool = WeightedPool {
+            balance_in: U256::from(1000) * SCALE_18,
+            weight_in: U256::from(80) * SCALE_18,
+            balance_out: U256::from(1000) * SCALE_18,
+            weight_out: U256::from(20) * SCALE_18,
+        };
+        let out = weighted_out(&pool, U256::from(100) * SCALE_18, U256::from(30));
+        assert!(out > U256::ZERO && out < U256::from(1000) * SCALE_18);
+    }
+
+    #[test]
+    fn test_checked_arithmetic_boundaries() {
+        // Multiplication and addition at the 256-bit boundary report overflow
+        // instead of wrapping.
+        assert_eq!(checked_mul(U256::MAX, U256::from(2)), Err(MathError::Overflow));
+        assert_eq!(checked_add(U256::MAX, U256::from(1)), Err(MathError::Overflow));
+
+        // Values that fit are returned untouched.
+        assert_eq!(checked_mul(U256::from(7), U256::from(6)), Ok(U256::from(42)));
+        assert_eq!(
+            checked_add(U256::MAX - U256::from(1), U256::from(1)),
+            Ok(U256::MAX)
+        );
+    }
+
+    #[test]
+    fn test_ln_fixed_sign() {
+        // ln(1) = 0, stored non-negative.
+        assert_eq!(ln_fixed(SCALE_18), I256Fixed::default());
+        // ln(2) > 0.
+        let l2 = ln_fixed(U256::from(2) * SCALE_18);
+        assert!(!l2.negative && l2.value > U256::ZERO);
+        // ln(0.5) = -ln(2): same magnitude, negative.
+        let half = SCALE_18 / U256::from(2);
+        let lhalf = ln_fixed(half);
+        assert!(lhalf.negative);
+        assert_eq!(lhalf.value, l2.value);
+    }
+
+    #[test]
+    fn test_i256_fixed_arithmetic() {
+        let one = I256Fixed::from_parts(SCALE_18, false);
+        let two = I256Fixed::from_parts(U256::from(2) * SCALE_18, false);
+        // 1 - 2 = -1
+        let neg_one = one.clone().sub(two.clone());
+        assert!(neg_one.negative && neg_one.value == SCALE_18);
+        // -1 + 2 = 1
+        assert_eq!(neg_one.clone().add(two.clone()), one);
+        // (-1) * 2 = -2
+        assert_eq!(neg_one.mul(two.clone()), I256Fixed::from_parts(U256::from(2) * SCALE_18, true));
+        // 2 / 2 = 1
+        assert_eq!(two.clone().div(two), one);
+        // from_diff picks up the sign
+        assert!(I256Fixed::from_diff(U256::from(1), U256::from(3)).negative);
+    }
+
+    #[test]
+    fn test_impermanent_loss_is_negative() {
+        // A price move away from parity produces a negative loss magnitude.
+        let engine_il = {
+            // Call through the free math rather than the SDK-bound method.
+            let initial = SCALE_18;
+            let current = U256::from(4) * SCALE_18;
+            let price_ratio = mul_div(current, SCALE_18, initial);
+            let sqrt_ratio = sqrt_fixed(price_ratio);
+            let numerator = U256::from(2) * sqrt_ratio;
+            let il_factor = mul_div(numerator, SCALE_18, SCALE_18 + price_ratio);
+            if il_factor < SCALE_18 {
+                I256Fixed::from_parts(mul_div(SCALE_18 - il_factor, BASIS_POINTS, SCALE_18), true)
+            } else {
+                I256Fixed::default()
+            }
+        };
+        assert!(engine_il.negative && engine_il.value > U256::ZERO);
+    }
+
     #[test]
     fn test_exp_approximation() {
         // e^0 = 1